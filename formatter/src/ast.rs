@@ -0,0 +1,92 @@
+//! AST for the subset of Aspen SQLplus the formatter understands deeply.
+//!
+//! `SELECT` statements get a full grammar so the pretty-printer can make
+//! real layout decisions about projections, joins, `WHERE`, and `CASE`.
+//! Everything else (DML other than `SELECT`, DDL, `BEGIN...END` blocks,
+//! procedural statements) is kept as [`Statement::Other`], a token span
+//! that still goes through keyword casing and operator spacing but is not
+//! restructured into a grammar of its own. Widening the deeply-understood
+//! grammar is future work; in the meantime `sqlfmt` still threads a
+//! `BEGIN`/`CASE`/`END` nesting depth across these statements so procedural
+//! bodies indent correctly even without a full AST for them.
+
+use crate::lexer::Token;
+
+#[derive(Debug, Clone)]
+pub enum Statement {
+    Select(Box<SelectStmt>),
+    Other(Vec<Token>),
+}
+
+#[derive(Debug, Clone)]
+pub struct SelectStmt {
+    pub distinct: bool,
+    pub projection: Vec<SelectItem>,
+    pub from: Vec<FromItem>,
+    pub where_clause: Option<Expr>,
+    pub group_by: Vec<Expr>,
+    pub having: Option<Expr>,
+    pub order_by: Vec<Expr>,
+    pub limit: Option<Expr>,
+    pub offset: Option<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SelectItem {
+    pub expr: Expr,
+    pub alias: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FromItem {
+    pub expr: Expr,
+    pub alias: Option<String>,
+    pub joins: Vec<Join>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Join {
+    pub kind: String, // "JOIN", "LEFT JOIN", "INNER JOIN", ...
+    pub table: Expr,
+    pub alias: Option<String>,
+    pub on: Option<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Ident(String),
+    QuotedIdent(String),
+    Number(String),
+    StringLit(String),
+    /// `left OP right`, e.g. `a = b`, `a AND b`.
+    BinOp {
+        left: Box<Expr>,
+        op: String,
+        right: Box<Expr>,
+    },
+    /// `NOT expr`.
+    Not(Box<Expr>),
+    /// `-expr` or `+expr` (a leading sign on a primary expression, as in
+    /// `WHERE id = -1`), distinct from the binary `+`/`-` in [`Expr::BinOp`].
+    Unary { op: String, expr: Box<Expr> },
+    /// `name(args...)`.
+    Call { name: String, args: Vec<Expr> },
+    Case {
+        operand: Option<Box<Expr>>,
+        whens: Vec<(Expr, Expr)>,
+        else_branch: Option<Box<Expr>>,
+    },
+    /// `expr IN (list...)`, list may itself be a subquery.
+    In {
+        expr: Box<Expr>,
+        negated: bool,
+        list: Vec<Expr>,
+    },
+    /// A parenthesized expression or subquery, printed as `(inner)`.
+    Paren(Box<Expr>),
+    /// A nested `SELECT` appearing where an expression is expected.
+    SubQuery(Box<SelectStmt>),
+    /// Anything the expression parser didn't recognize, kept verbatim so we
+    /// never lose or corrupt content we don't have a rule for.
+    Raw(String),
+}
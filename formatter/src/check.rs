@@ -0,0 +1,100 @@
+//! `--check`: the CI gate `cargo fmt --check` provides, but for SQL.
+//!
+//! Formats each input in memory and compares it against the file on disk
+//! without writing anything back. Any file whose formatted output differs
+//! is reported and makes the run exit non-zero. As a side effect we also
+//! run the formatter's own output back through itself once and assert it
+//! comes out unchanged (`format(format(x)) == format(x)`); a mismatch means
+//! the pretty printer itself has a bug, reported as an `Info` diagnostic
+//! rather than silently shipping drifting output.
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use crate::diagnostics::{self, Diagnostic, DiagnosticSeverity};
+use crate::file_lines::FileLines;
+use crate::options::Options;
+use crate::sqlfmt;
+
+pub fn run(files: &[PathBuf], opts: &Options, file_lines: &FileLines) -> Result<()> {
+    let mut drifted = false;
+
+    for path in files {
+        let input = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read file {}", path.display()))?;
+        let formatted = sqlfmt::format_sql_restricted(&input, opts, Some(path), file_lines)?;
+
+        // `format_sql` trims trailing whitespace from its output, but files
+        // on disk conventionally end with a newline; comparing against the
+        // raw trailing-newline-sensitive `input` here would report an
+        // already-formatted file as drifted on every run.
+        if formatted != input.trim_end() {
+            drifted = true;
+            println!("{}", path.display());
+        }
+
+        let reformatted = sqlfmt::format_sql_restricted(&formatted, opts, Some(path), file_lines)?;
+        if reformatted != formatted {
+            drifted = true;
+            let diagnostic = Diagnostic {
+                line: 1,
+                column: 1,
+                end_line: 1,
+                end_column: 1,
+                message: format!(
+                    "formatting {} is not idempotent (format(format(x)) != format(x))",
+                    path.display()
+                ),
+                severity: DiagnosticSeverity::Info,
+                code: "idempotency-regression".to_string(),
+            };
+            eprint!("{}", diagnostics::format_diagnostics(&[diagnostic]));
+        }
+    }
+
+    if drifted {
+        anyhow::bail!("formatting check failed");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::{CommaStyle, IndentStyle};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn opts() -> Options {
+        Options {
+            line_width: 88,
+            indent: IndentStyle::Two,
+            uppercase_keywords: true,
+            comma_style: CommaStyle::Trailing,
+        }
+    }
+
+    /// Writes `contents` to a fresh file under the system temp dir so tests
+    /// can run `check::run` against a real path without clobbering each
+    /// other when run in parallel.
+    fn write_temp_file(name: &str, contents: &str) -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let id = COUNTER.fetch_add(1, Ordering::SeqCst);
+        let path = std::env::temp_dir().join(format!("sqlfmt-check-test-{}-{}", id, name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn already_formatted_file_with_trailing_newline_does_not_drift() {
+        let path = write_temp_file("good.sql", "SELECT a, b FROM t WHERE x = 1;\n");
+        assert!(run(&[path], &opts(), &FileLines::default()).is_ok());
+    }
+
+    #[test]
+    fn unformatted_file_is_reported_and_fails() {
+        let path = write_temp_file("bad.sql", "select a,b from t where x=1;\n");
+        assert!(run(&[path], &opts(), &FileLines::default()).is_err());
+    }
+}
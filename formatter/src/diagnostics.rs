@@ -1,14 +1,26 @@
-use regex::Regex;
-use std::collections::HashMap;
+//! Variable declaration/usage analysis for `DECLARE`/`LOCAL`/`SET` locals.
+//!
+//! Tracks `BEGIN...END` blocks as nested scopes (`CASE...END` shares the
+//! `END` keyword but never opens one) so a variable re-declared in a
+//! sibling block isn't wrongly flagged `duplicate-variable`, and walks
+//! declarations and uses in source order so a variable used before its
+//! `DECLARE`/`LOCAL`/`SET` — or never declared anywhere visible from that
+//! point — is reported precisely instead of as a flat whole-file count.
 
-#[derive(Debug, Clone, PartialEq)]
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::lexer::{self, Token, TokenKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DiagnosticSeverity {
     Error,
     Warning,
     Info,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Diagnostic {
     pub line: usize,
     pub column: usize,
@@ -20,103 +32,256 @@ pub struct Diagnostic {
 }
 
 #[derive(Debug, Clone)]
-pub struct Variable {
-    pub name: String,
-    pub declaration_type: String, // DECLARE, SET, LOCAL
-    pub line: usize,
-    pub column: usize,
-    pub end_column: usize,
+struct Variable {
+    name: String,
+    line: usize,
+    column: usize,
+    end_column: usize,
+    used: bool,
+}
+
+/// One `BEGIN...END` block's declarations (the whole file is scope `0`),
+/// keyed by lowercased name since Aspen SQLplus identifiers are
+/// case-insensitive; each declaration keeps the token index it was
+/// declared at so usage resolution can tell whether a reference comes
+/// before or after it, and a name declared twice in the same scope keeps
+/// both entries so the second can be flagged `duplicate-variable`.
+#[derive(Debug, Default)]
+struct Scope {
+    parent: Option<usize>,
+    declarations: HashMap<String, Vec<(usize, Variable)>>,
+}
+
+/// A stack frame opened by `BEGIN` (introduces a scope) or `CASE` (shares
+/// `END` with `BEGIN` but doesn't introduce a scope of its own); `END` pops
+/// whichever frame is on top, so a `CASE` nested inside a block doesn't
+/// prematurely close the block's scope.
+enum Frame {
+    Scope(usize),
+    Case,
+}
+
+/// Where a use of an already-known variable name resolves relative to its
+/// declaration(s) in the enclosing scope chain.
+enum Resolution {
+    /// Declared in `scope` at or before this point.
+    Declared(usize),
+    /// Declared later in the visible scope chain, but not yet.
+    DeclaredLater,
+    /// Declared somewhere in the file, but not in any scope visible here.
+    OutOfScope,
 }
 
 pub fn analyze_variables(input: &str) -> Vec<Diagnostic> {
+    let tokens: Vec<Token> = lexer::tokenize(input)
+        .into_iter()
+        .filter(|t| !t.is_trivia())
+        .collect();
+
+    let mut scopes = vec![Scope::default()];
+    let mut frames = vec![Frame::Scope(0)];
+    let mut token_scope = Vec::with_capacity(tokens.len());
+    let mut is_decl_name = vec![false; tokens.len()];
+
+    for idx in 0..tokens.len() {
+        let tok = &tokens[idx];
+        if tok.kind == TokenKind::Keyword {
+            match tok.text.to_ascii_lowercase().as_str() {
+                "begin" => {
+                    let parent = current_scope(&frames);
+                    let id = scopes.len();
+                    scopes.push(Scope {
+                        parent: Some(parent),
+                        declarations: HashMap::new(),
+                    });
+                    frames.push(Frame::Scope(id));
+                }
+                "case" => frames.push(Frame::Case),
+                // The root scope (frames[0]) is never popped, so a stray
+                // `END` with nothing open can't underflow the stack.
+                "end" if frames.len() > 1 => {
+                    frames.pop();
+                }
+                _ => {}
+            }
+        }
+
+        let scope_id = current_scope(&frames);
+        token_scope.push(scope_id);
+
+        if is_decl_keyword(tok) {
+            if let Some(name_tok) = tokens.get(idx + 1) {
+                if name_tok.kind == TokenKind::Ident {
+                    let key = name_tok.text.to_ascii_lowercase();
+                    // `SET` doubles as "declare" for a name seen for the
+                    // first time, but on a name already declared it's a
+                    // plain reassignment — not a second declaration site —
+                    // so it's left for the usage pass below to resolve.
+                    let is_reassignment = tok.text.eq_ignore_ascii_case("set")
+                        && matches!(
+                            resolve(&scopes, scope_id, &key, idx + 1),
+                            Resolution::Declared(_)
+                        );
+                    if !is_reassignment {
+                        is_decl_name[idx + 1] = true;
+                        let variable = Variable {
+                            name: name_tok.text.clone(),
+                            line: name_tok.line,
+                            column: name_tok.column,
+                            end_column: name_tok.column + name_tok.text.chars().count(),
+                            used: false,
+                        };
+                        scopes[scope_id]
+                            .declarations
+                            .entry(key)
+                            .or_default()
+                            .push((idx + 1, variable));
+                    }
+                }
+            }
+        }
+    }
+
+    diagnose(&tokens, &token_scope, &is_decl_name, scopes)
+}
+
+fn is_decl_keyword(tok: &Token) -> bool {
+    tok.kind == TokenKind::Keyword
+        && matches!(
+            tok.text.to_ascii_lowercase().as_str(),
+            "declare" | "set" | "local"
+        )
+}
+
+fn current_scope(frames: &[Frame]) -> usize {
+    frames
+        .iter()
+        .rev()
+        .find_map(|f| match f {
+            Frame::Scope(id) => Some(*id),
+            Frame::Case => None,
+        })
+        .unwrap_or(0)
+}
+
+fn diagnose(
+    tokens: &[Token],
+    token_scope: &[usize],
+    is_decl_name: &[bool],
+    mut scopes: Vec<Scope>,
+) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
-    let lines: Vec<&str> = input.lines().collect();
-
-    // Regex to capture variable declarations: DECLARE var_name / SET var_name / LOCAL var_name
-    let decl_regex =
-        Regex::new(r"(?i)\b(DECLARE|SET|LOCAL)\s+([a-zA-Z_][a-zA-Z0-9_]*)").expect("Invalid regex");
-
-    let mut variables: HashMap<String, Vec<Variable>> = HashMap::new();
-    let mut all_variables: Vec<Variable> = Vec::new();
-
-    // Find all variable declarations
-    for (line_idx, line) in lines.iter().enumerate() {
-        for captures in decl_regex.captures_iter(line) {
-            if let (Some(decl_type), Some(var_name)) = (captures.get(1), captures.get(2)) {
-                let variable = Variable {
-                    name: var_name.as_str().to_string(),
-                    declaration_type: decl_type.as_str().to_ascii_uppercase(),
-                    line: line_idx + 1,           // 1-based line numbers
-                    column: var_name.start() + 1, // 1-based column numbers
-                    end_column: var_name.end() + 1,
-                };
-
-                let var_key = var_name.as_str().to_lowercase();
-                variables
-                    .entry(var_key.clone())
-                    .or_insert_with(Vec::new)
-                    .push(variable.clone());
-                all_variables.push(variable);
+
+    for scope in &scopes {
+        for decls in scope.declarations.values() {
+            for (_, var) in decls.iter().skip(1) {
+                diagnostics.push(Diagnostic {
+                    line: var.line,
+                    column: var.column,
+                    end_line: var.line,
+                    end_column: var.end_column,
+                    message: format!("Variable '{}' has already been declared", var.name),
+                    severity: DiagnosticSeverity::Error,
+                    code: "duplicate-variable".to_string(),
+                });
+            }
+        }
+    }
+
+    // Only second-guess identifiers known to be variables somewhere in the
+    // file; everything else (columns, tables, aliases) is left alone.
+    let known_names: HashSet<String> = scopes
+        .iter()
+        .flat_map(|s| s.declarations.keys().cloned())
+        .collect();
+
+    for (idx, tok) in tokens.iter().enumerate() {
+        if tok.kind != TokenKind::Ident || is_decl_name[idx] {
+            continue;
+        }
+        let key = tok.text.to_ascii_lowercase();
+        if !known_names.contains(&key) {
+            continue;
+        }
+
+        match resolve(&scopes, token_scope[idx], &key, idx) {
+            Resolution::Declared(owner_scope) => {
+                if let Some(decls) = scopes[owner_scope].declarations.get_mut(&key) {
+                    if let Some((_, var)) = decls
+                        .iter_mut()
+                        .rev()
+                        .find(|(decl_idx, _)| *decl_idx <= idx)
+                    {
+                        var.used = true;
+                    }
+                }
             }
+            Resolution::DeclaredLater => diagnostics.push(Diagnostic {
+                line: tok.line,
+                column: tok.column,
+                end_line: tok.line,
+                end_column: tok.column + tok.text.chars().count(),
+                message: format!("Variable '{}' is used before it is declared", tok.text),
+                severity: DiagnosticSeverity::Error,
+                code: "used-before-declaration".to_string(),
+            }),
+            Resolution::OutOfScope => diagnostics.push(Diagnostic {
+                line: tok.line,
+                column: tok.column,
+                end_line: tok.line,
+                end_column: tok.column + tok.text.chars().count(),
+                message: format!("Variable '{}' is not declared in this scope", tok.text),
+                severity: DiagnosticSeverity::Error,
+                code: "undeclared-variable".to_string(),
+            }),
         }
     }
 
-    // Check for duplicate declarations
-    for (var_name, declarations) in &variables {
-        if declarations.len() > 1 {
-            // Mark all declarations after the first as duplicates
-            for (i, var) in declarations.iter().enumerate() {
-                if i > 0 {
-                    // Skip the first declaration
+    for scope in &scopes {
+        for decls in scope.declarations.values() {
+            for (_, var) in decls {
+                if !var.used {
                     diagnostics.push(Diagnostic {
                         line: var.line,
                         column: var.column,
                         end_line: var.line,
                         end_column: var.end_column,
-                        message: format!("Variable '{}' has already been declared", var.name),
-                        severity: DiagnosticSeverity::Error,
-                        code: "duplicate-variable".to_string(),
+                        message: format!("Unused variable '{}'", var.name),
+                        severity: DiagnosticSeverity::Warning,
+                        code: "unused-variable".to_string(),
                     });
                 }
             }
         }
     }
 
-    // Check for unused variables
-    for variable in &all_variables {
-        if !is_variable_used(input, &variable.name, &all_variables) {
-            diagnostics.push(Diagnostic {
-                line: variable.line,
-                column: variable.column,
-                end_line: variable.line,
-                end_column: variable.end_column,
-                message: format!("Unused variable '{}'", variable.name),
-                severity: DiagnosticSeverity::Warning,
-                code: "unused-variable".to_string(),
-            });
-        }
-    }
-
+    diagnostics.sort_by_key(|d| (d.line, d.column));
     diagnostics
 }
 
-fn is_variable_used(input: &str, var_name: &str, all_variables: &[Variable]) -> bool {
-    let usage_regex =
-        Regex::new(&format!(r"(?i)\b{}\b", regex::escape(var_name))).expect("Invalid regex");
-
-    let mut usage_count = 0;
-    for _ in usage_regex.find_iter(input) {
-        usage_count += 1;
+/// Walks the scope chain from `scope_id` up to the root looking for a
+/// declaration of `key` visible at token `idx`.
+fn resolve(scopes: &[Scope], mut scope_id: usize, key: &str, idx: usize) -> Resolution {
+    let mut declared_later = false;
+    loop {
+        if let Some(decls) = scopes[scope_id].declarations.get(key) {
+            if decls.iter().any(|(decl_idx, _)| *decl_idx <= idx) {
+                return Resolution::Declared(scope_id);
+            }
+            if decls.iter().any(|(decl_idx, _)| *decl_idx > idx) {
+                declared_later = true;
+            }
+        }
+        match scopes[scope_id].parent {
+            Some(parent) => scope_id = parent,
+            None => break,
+        }
+    }
+    if declared_later {
+        Resolution::DeclaredLater
+    } else {
+        Resolution::OutOfScope
     }
-
-    // Count how many times this variable is declared
-    let declaration_count = all_variables
-        .iter()
-        .filter(|v| v.name.to_lowercase() == var_name.to_lowercase())
-        .count();
-
-    // If usage count is greater than declaration count, then it's used
-    usage_count > declaration_count
 }
 
 pub fn format_diagnostics(diagnostics: &[Diagnostic]) -> String {
@@ -203,4 +368,100 @@ LOCAL I real;
 
         assert_eq!(duplicates.len(), 1);
     }
+
+    #[test]
+    fn same_name_in_sibling_begin_blocks_is_not_a_duplicate() {
+        let input = r#"
+BEGIN
+  LOCAL i real;
+  SELECT i FROM t;
+END;
+BEGIN
+  LOCAL i real;
+  SELECT i FROM t;
+END;
+"#;
+
+        let diagnostics = analyze_variables(input);
+        assert!(diagnostics.iter().all(|d| d.code != "duplicate-variable"));
+    }
+
+    #[test]
+    fn case_end_does_not_close_the_enclosing_begin_block() {
+        let input = r#"
+BEGIN
+  LOCAL i real;
+  SELECT CASE WHEN 1 = 1 THEN i ELSE 0 END FROM t;
+END;
+"#;
+
+        let diagnostics = analyze_variables(input);
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.code != "undeclared-variable" && d.code != "unused-variable"));
+    }
+
+    #[test]
+    fn variable_used_before_its_declaration_in_the_same_scope_is_flagged() {
+        let input = r#"
+BEGIN
+  SELECT i FROM t;
+  LOCAL i real;
+END;
+"#;
+
+        let diagnostics = analyze_variables(input);
+        let flagged: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.code == "used-before-declaration")
+            .collect();
+        assert_eq!(flagged.len(), 1);
+    }
+
+    #[test]
+    fn variable_local_to_a_sibling_scope_is_undeclared_here() {
+        let input = r#"
+BEGIN
+  LOCAL i real;
+END;
+BEGIN
+  SELECT i FROM t;
+END;
+"#;
+
+        let diagnostics = analyze_variables(input);
+        let flagged: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.code == "undeclared-variable")
+            .collect();
+        assert_eq!(flagged.len(), 1);
+    }
+
+    #[test]
+    fn variable_declared_in_an_outer_scope_is_visible_to_an_inner_one() {
+        let input = r#"
+LOCAL i real;
+BEGIN
+  SELECT i FROM t;
+END;
+"#;
+
+        let diagnostics = analyze_variables(input);
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.code != "undeclared-variable" && d.code != "unused-variable"));
+    }
+
+    #[test]
+    fn set_reassigning_an_already_declared_variable_is_a_use_not_a_redeclaration() {
+        let input = r#"
+LOCAL x integer;
+SET x = 2;
+"#;
+
+        let diagnostics = analyze_variables(input);
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.code != "duplicate-variable" && d.code != "unused-variable"));
+    }
 }
@@ -0,0 +1,207 @@
+//! A small Wadler/Hughes-style algebraic pretty-printer.
+//!
+//! Statements are lowered into a `Doc` tree built from a handful of
+//! combinators (`text`, `line`, `nest`, `concat`, `group`) and then laid out
+//! against `Options::line_width`. A `group` renders flat on one line if it
+//! fits at the current column, otherwise every `line` inside it breaks into
+//! a newline followed by the current indent. This keeps clause wrapping
+//! consistent and lets every future layout decision (vertical lists,
+//! range-limited formatting) hang off the same `Doc`/layout machinery
+//! instead of ad hoc string surgery.
+
+#[derive(Debug, Clone)]
+pub enum Doc {
+    /// Literal text, must not contain `\n`.
+    Text(String),
+    /// A breakable space: a single space when the enclosing group is flat,
+    /// a newline + current indent when the enclosing group is broken.
+    Line,
+    /// Like `Line` but renders as nothing (not a space) when flat.
+    SoftLine,
+    /// Adds `n` to the indent applied after every newline produced inside `doc`.
+    Nest(usize, Box<Doc>),
+    /// Sequence of docs laid out one after another.
+    Concat(Vec<Doc>),
+    /// Tries to lay `doc` out flat; if it doesn't fit in the remaining
+    /// width, every `Line`/`SoftLine` inside breaks instead.
+    Group(Box<Doc>),
+}
+
+pub fn text(s: impl Into<String>) -> Doc {
+    Doc::Text(s.into())
+}
+
+pub fn line() -> Doc {
+    Doc::Line
+}
+
+pub fn softline() -> Doc {
+    Doc::SoftLine
+}
+
+pub fn nest(n: usize, doc: Doc) -> Doc {
+    Doc::Nest(n, Box::new(doc))
+}
+
+pub fn concat(docs: Vec<Doc>) -> Doc {
+    Doc::Concat(docs)
+}
+
+pub fn group(doc: Doc) -> Doc {
+    Doc::Group(Box::new(doc))
+}
+
+/// Joins `docs` with `sep` placed between each pair (not after the last).
+pub fn join(sep: Doc, docs: Vec<Doc>) -> Doc {
+    let mut out = Vec::new();
+    for (i, d) in docs.into_iter().enumerate() {
+        if i > 0 {
+            out.push(sep.clone());
+        }
+        out.push(d);
+    }
+    Doc::Concat(out)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+/// Renders `doc` at the given `line_width`, starting at column 0.
+pub fn render(doc: &Doc, line_width: usize) -> String {
+    let mut out = String::new();
+    let mut column = 0usize;
+    // Work list of (indent, mode, doc) entries, processed back-to-front
+    // like a stack so earlier docs are emitted first.
+    let mut work: Vec<(usize, Mode, &Doc)> = vec![(0, Mode::Break, doc)];
+
+    while let Some((indent, mode, d)) = work.pop() {
+        match d {
+            Doc::Text(s) => {
+                out.push_str(s);
+                column += s.chars().count();
+            }
+            Doc::Line => match mode {
+                Mode::Flat => {
+                    out.push(' ');
+                    column += 1;
+                }
+                Mode::Break => {
+                    out.push('\n');
+                    out.push_str(&" ".repeat(indent));
+                    column = indent;
+                }
+            },
+            Doc::SoftLine => match mode {
+                Mode::Flat => {}
+                Mode::Break => {
+                    out.push('\n');
+                    out.push_str(&" ".repeat(indent));
+                    column = indent;
+                }
+            },
+            Doc::Nest(n, inner) => {
+                work.push((indent + n, mode, inner));
+            }
+            Doc::Concat(docs) => {
+                for inner in docs.iter().rev() {
+                    work.push((indent, mode, inner));
+                }
+            }
+            Doc::Group(inner) => {
+                let flat_mode = if fits(line_width.saturating_sub(column), inner, &work) {
+                    Mode::Flat
+                } else {
+                    Mode::Break
+                };
+                work.push((indent, flat_mode, inner));
+            }
+        }
+    }
+
+    out
+}
+
+/// Checks whether `doc`, laid out flat, plus the remaining work-list items
+/// (laid out in their own modes) stay within `remaining` columns before the
+/// next hard break. This is the classic forward scan used to resolve
+/// `group`s without backtracking the whole document.
+fn fits(remaining: usize, doc: &Doc, rest: &[(usize, Mode, &Doc)]) -> bool {
+    let mut remaining = remaining as isize;
+    let mut stack: Vec<(Mode, &Doc)> = vec![(Mode::Flat, doc)];
+    let mut rest_idx = rest.len();
+
+    loop {
+        if remaining < 0 {
+            return false;
+        }
+        let (mode, d) = match stack.pop() {
+            Some(item) => item,
+            None => {
+                if rest_idx == 0 {
+                    return true;
+                }
+                rest_idx -= 1;
+                let (_, m, d) = rest[rest_idx];
+                stack.push((m, d));
+                continue;
+            }
+        };
+        match d {
+            Doc::Text(s) => remaining -= s.chars().count() as isize,
+            Doc::Line => match mode {
+                Mode::Flat => remaining -= 1,
+                Mode::Break => return true,
+            },
+            Doc::SoftLine => match mode {
+                Mode::Flat => {}
+                Mode::Break => return true,
+            },
+            Doc::Nest(_, inner) => stack.push((mode, inner)),
+            Doc::Concat(docs) => {
+                for inner in docs.iter().rev() {
+                    stack.push((mode, inner));
+                }
+            }
+            Doc::Group(inner) => stack.push((Mode::Flat, inner)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_group_stays_flat() {
+        let doc = group(concat(vec![
+            text("SELECT"),
+            nest(2, concat(vec![line(), text("a, b, c")])),
+        ]));
+        assert_eq!(render(&doc, 40), "SELECT a, b, c");
+    }
+
+    #[test]
+    fn overflowing_group_breaks_with_indent() {
+        let doc = group(concat(vec![
+            text("SELECT"),
+            nest(2, concat(vec![line(), text("a, b, c, d, e, f, g, h")])),
+        ]));
+        assert_eq!(render(&doc, 10), "SELECT\n  a, b, c, d, e, f, g, h");
+    }
+
+    #[test]
+    fn nested_groups_break_independently() {
+        let inner = group(concat(vec![text("("), softline(), text("x")]));
+        let doc = group(concat(vec![
+            text("WHERE"),
+            nest(2, concat(vec![line(), inner, text(")")])),
+        ]));
+        // Fits entirely flat.
+        assert_eq!(render(&doc, 40), "WHERE (x)");
+        // The outer group breaks, while the inner one still fits flat.
+        assert_eq!(render(&doc, 6), "WHERE\n  (x)");
+    }
+}
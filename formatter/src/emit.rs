@@ -0,0 +1,142 @@
+//! Pluggable output emitters, selected with `--emit`.
+//!
+//! Mirrors the stdout/diff/json/checkstyle split rustfmt exposes: the
+//! `files`/`stdout`/`diff` emitters are for humans at a terminal, while
+//! `json` and `checkstyle` give editors and CI a structured payload built
+//! from the same [`Diagnostic`]s the terminal emitters only print as text.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::diagnostics::{self, Diagnostic};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum EmitMode {
+    /// Write the formatted output back to each input file.
+    Files,
+    /// Print the formatted output to stdout.
+    Stdout,
+    /// Print a unified diff between the original and formatted output.
+    Diff,
+    /// Print one JSON array per run, holding formatted text + diagnostics per file.
+    Json,
+    /// Print a Checkstyle-compatible XML report built from the diagnostics.
+    Checkstyle,
+}
+
+/// Everything an emitter needs to know about one formatted input file.
+pub struct FileResult {
+    pub path: PathBuf,
+    pub original: String,
+    pub formatted: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+pub fn emit(mode: EmitMode, results: &[FileResult]) -> Result<()> {
+    match mode {
+        EmitMode::Files => emit_files(results),
+        EmitMode::Stdout => emit_stdout(results),
+        EmitMode::Diff => emit_diff(results),
+        EmitMode::Json => emit_json(results),
+        EmitMode::Checkstyle => emit_checkstyle(results),
+    }
+}
+
+fn emit_files(results: &[FileResult]) -> Result<()> {
+    for result in results {
+        fs::write(&result.path, &result.formatted)
+            .with_context(|| format!("Failed to write file {}", result.path.display()))?;
+        print_diagnostics(result);
+    }
+    Ok(())
+}
+
+fn emit_stdout(results: &[FileResult]) -> Result<()> {
+    for result in results {
+        print!("{}", result.formatted);
+        print_diagnostics(result);
+    }
+    Ok(())
+}
+
+fn emit_diff(results: &[FileResult]) -> Result<()> {
+    for result in results {
+        let changes = similar::TextDiff::from_lines(&result.original, &result.formatted);
+        let name = path_display(&result.path);
+        print!("{}", changes.unified_diff().header(&name, &name));
+        print_diagnostics(result);
+    }
+    Ok(())
+}
+
+/// Prints variable-analysis diagnostics in the plain `line:col:col: severity:
+/// msg [code]` text form, to stderr so it doesn't interleave with formatted
+/// output on stdout.
+fn print_diagnostics(result: &FileResult) {
+    if !result.diagnostics.is_empty() {
+        eprint!("{}", diagnostics::format_diagnostics(&result.diagnostics));
+    }
+}
+
+#[derive(Serialize)]
+struct JsonFileReport<'a> {
+    path: String,
+    formatted: &'a str,
+    diagnostics: &'a [Diagnostic],
+}
+
+fn emit_json(results: &[FileResult]) -> Result<()> {
+    let report: Vec<JsonFileReport> = results
+        .iter()
+        .map(|r| JsonFileReport {
+            path: path_display(&r.path),
+            formatted: &r.formatted,
+            diagnostics: &r.diagnostics,
+        })
+        .collect();
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+fn emit_checkstyle(results: &[FileResult]) -> Result<()> {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"1.0\">\n");
+    for result in results {
+        out.push_str(&format!("  <file name=\"{}\">\n", xml_escape(&path_display(&result.path))));
+        for d in &result.diagnostics {
+            let severity = match d.severity {
+                crate::diagnostics::DiagnosticSeverity::Error => "error",
+                crate::diagnostics::DiagnosticSeverity::Warning => "warning",
+                crate::diagnostics::DiagnosticSeverity::Info => "info",
+            };
+            out.push_str(&format!(
+                "    <error line=\"{}\" column=\"{}\" severity=\"{}\" message=\"{}\" source=\"{}\"/>\n",
+                d.line,
+                d.column,
+                severity,
+                xml_escape(&d.message),
+                xml_escape(&d.code),
+            ));
+        }
+        out.push_str("  </file>\n");
+    }
+    out.push_str("</checkstyle>\n");
+    print!("{}", out);
+    Ok(())
+}
+
+fn path_display(path: &Path) -> String {
+    path.display().to_string()
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,122 @@
+//! `--file-lines`: restrict formatting to a set of line ranges per file, the
+//! same `file_lines` feature rustfmt exposes for formatting just the lines
+//! you touched instead of an entire file.
+//!
+//! Accepts the flag repeated, each occurrence either a plain `FILE:START-END`
+//! range or a JSON array of `{"file": ..., "range": [start, end]}` objects;
+//! all occurrences are merged. When no `--file-lines` flags are given at all, every file
+//! is formatted in full; once any are given, a file with no entry of its own
+//! is left completely untouched rather than partially restricted.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct JsonRange {
+    file: String,
+    range: (usize, usize),
+}
+
+#[derive(Debug, Default)]
+pub struct FileLines {
+    ranges: HashMap<String, Vec<(usize, usize)>>,
+}
+
+impl FileLines {
+    /// Parses every `--file-lines` occurrence, each either a `FILE:START-END`
+    /// string or a JSON array of `{"file", "range"}` objects.
+    pub fn parse(args: &[String]) -> Result<FileLines> {
+        let mut ranges: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+
+        for arg in args {
+            let arg = arg.trim();
+            if arg.starts_with('[') {
+                let entries: Vec<JsonRange> = serde_json::from_str(arg)
+                    .with_context(|| format!("invalid --file-lines JSON: {}", arg))?;
+                for entry in entries {
+                    ranges.entry(entry.file).or_default().push(entry.range);
+                }
+            } else {
+                let (file, range) = parse_plain_range(arg)?;
+                ranges.entry(file).or_default().push(range);
+            }
+        }
+
+        Ok(FileLines { ranges })
+    }
+
+    /// Whether a statement spanning `start_line..=end_line` in `path` should
+    /// be formatted. With no `--file-lines` flags at all, everything is
+    /// formatted; with no `path` (stdin has none), `file_lines` doesn't
+    /// apply and everything is formatted too.
+    pub fn should_format(&self, path: Option<&Path>, start_line: usize, end_line: usize) -> bool {
+        if self.ranges.is_empty() {
+            return true;
+        }
+        let Some(path) = path else {
+            return true;
+        };
+        match self.ranges.get(&path.to_string_lossy().into_owned()) {
+            Some(spans) => spans.iter().any(|&(s, e)| start_line <= e && end_line >= s),
+            None => false,
+        }
+    }
+}
+
+fn parse_plain_range(arg: &str) -> Result<(String, (usize, usize))> {
+    let (file, range) = arg
+        .rsplit_once(':')
+        .with_context(|| format!("expected FILE:START-END, got `{}`", arg))?;
+    let (start, end) = range
+        .split_once('-')
+        .with_context(|| format!("expected FILE:START-END, got `{}`", arg))?;
+    let start: usize = start
+        .trim()
+        .parse()
+        .with_context(|| format!("invalid start line in `{}`", arg))?;
+    let end: usize = end
+        .trim()
+        .parse()
+        .with_context(|| format!("invalid end line in `{}`", arg))?;
+    Ok((file.to_string(), (start, end)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_flags_means_every_file_is_formatted_in_full() {
+        let file_lines = FileLines::parse(&[]).unwrap();
+        assert!(file_lines.should_format(Some(Path::new("a.sql")), 1, 100));
+    }
+
+    #[test]
+    fn plain_range_restricts_the_named_file_only() {
+        let file_lines = FileLines::parse(&["a.sql:2-4".to_string()]).unwrap();
+        assert!(file_lines.should_format(Some(Path::new("a.sql")), 3, 3));
+        assert!(!file_lines.should_format(Some(Path::new("a.sql")), 10, 12));
+        assert!(!file_lines.should_format(Some(Path::new("b.sql")), 1, 1));
+    }
+
+    #[test]
+    fn json_array_form_covers_multiple_files() {
+        let file_lines = FileLines::parse(&[
+            r#"[{"file": "a.sql", "range": [1, 2]}, {"file": "b.sql", "range": [5, 9]}]"#
+                .to_string(),
+        ])
+        .unwrap();
+        assert!(file_lines.should_format(Some(Path::new("a.sql")), 1, 1));
+        assert!(file_lines.should_format(Some(Path::new("b.sql")), 5, 5));
+        assert!(!file_lines.should_format(Some(Path::new("b.sql")), 10, 10));
+    }
+
+    #[test]
+    fn no_path_is_unrestricted() {
+        let file_lines = FileLines::parse(&["a.sql:2-4".to_string()]).unwrap();
+        assert!(file_lines.should_format(None, 1, 1));
+    }
+}
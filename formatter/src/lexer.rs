@@ -0,0 +1,390 @@
+//! Tokenizer for Aspen SQLplus source.
+//!
+//! Splits input into spans of plain SQL, string/identifier literals, and
+//! comments up front so every later pass (keyword casing, the parser) can
+//! treat literal and comment bytes as opaque instead of risking a rewrite
+//! of their contents. This mirrors the "mask out strings and comments"
+//! approach most SQL formatters use before doing anything syntax-aware.
+
+pub const KEYWORDS: &[&str] = &[
+    "select",
+    "insert",
+    "update",
+    "delete",
+    "from",
+    "where",
+    "group",
+    "by",
+    "order",
+    "asc",
+    "desc",
+    "having",
+    "limit",
+    "offset",
+    "join",
+    "inner",
+    "left",
+    "right",
+    "full",
+    "outer",
+    "on",
+    "as",
+    "and",
+    "or",
+    "not",
+    "null",
+    "is",
+    "in",
+    "exists",
+    "case",
+    "when",
+    "then",
+    "else",
+    "end",
+    "create",
+    "table",
+    "view",
+    "function",
+    "procedure",
+    "if",
+    "begin",
+    "commit",
+    "rollback",
+    "union",
+    "all",
+    "distinct",
+    "with",
+    "over",
+    "write",
+    "partition",
+    "into",
+    "values",
+    "return",
+    "returns",
+    "declare",
+    "set",
+    "local",
+    "real",
+    "integer",
+    "record",
+    "do",
+    "char",
+    "abs",
+    "max",
+    "min",
+    "timestamp",
+];
+
+pub fn is_keyword(word: &str) -> bool {
+    KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(word))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Keyword,
+    Ident,
+    QuotedIdent,
+    Number,
+    StringLit,
+    Operator,
+    Comma,
+    LParen,
+    RParen,
+    Semicolon,
+    LineComment,
+    BlockComment,
+    Whitespace,
+    Other,
+}
+
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Token {
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self.kind,
+            TokenKind::Whitespace | TokenKind::LineComment | TokenKind::BlockComment
+        )
+    }
+}
+
+/// Scans `input` into a flat token stream, tracking 1-based line/column of
+/// each token's first byte. String literals (`'...'`, doubled `''` escapes),
+/// double-quoted identifiers, `--` line comments and `/* */` block comments
+/// are recognized as single opaque tokens so nothing downstream mutates
+/// their contents. An unterminated block comment runs to end of input
+/// rather than panicking or desyncing the scanner.
+pub fn tokenize(input: &str) -> Vec<Token> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0usize;
+    let mut line = 1usize;
+    let mut column = 1usize;
+
+    let advance = |i: &mut usize, line: &mut usize, column: &mut usize, chars: &[char]| {
+        if chars[*i] == '\n' {
+            *line += 1;
+            *column = 1;
+        } else {
+            *column += 1;
+        }
+        *i += 1;
+    };
+
+    while i < chars.len() {
+        let start_line = line;
+        let start_col = column;
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            let start = i;
+            while i < chars.len() && chars[i].is_whitespace() {
+                advance(&mut i, &mut line, &mut column, &chars);
+            }
+            tokens.push(Token {
+                kind: TokenKind::Whitespace,
+                text: chars[start..i].iter().collect(),
+                line: start_line,
+                column: start_col,
+            });
+            continue;
+        }
+
+        // Line comment: `-- ...` to end of line.
+        if c == '-' && i + 1 < chars.len() && chars[i + 1] == '-' {
+            let start = i;
+            while i < chars.len() && chars[i] != '\n' {
+                advance(&mut i, &mut line, &mut column, &chars);
+            }
+            tokens.push(Token {
+                kind: TokenKind::LineComment,
+                text: chars[start..i].iter().collect(),
+                line: start_line,
+                column: start_col,
+            });
+            continue;
+        }
+
+        // Block comment: `/* ... */`, tolerating an unterminated tail.
+        if c == '/' && i + 1 < chars.len() && chars[i + 1] == '*' {
+            let start = i;
+            advance(&mut i, &mut line, &mut column, &chars);
+            advance(&mut i, &mut line, &mut column, &chars);
+            while i < chars.len() && !(chars[i] == '*' && i + 1 < chars.len() && chars[i + 1] == '/') {
+                advance(&mut i, &mut line, &mut column, &chars);
+            }
+            if i < chars.len() {
+                advance(&mut i, &mut line, &mut column, &chars);
+                advance(&mut i, &mut line, &mut column, &chars);
+            } // else: unterminated, runs to EOF
+            tokens.push(Token {
+                kind: TokenKind::BlockComment,
+                text: chars[start..i].iter().collect(),
+                line: start_line,
+                column: start_col,
+            });
+            continue;
+        }
+
+        // Single-quoted string literal, with `''` as an escaped quote.
+        if c == '\'' {
+            let start = i;
+            advance(&mut i, &mut line, &mut column, &chars);
+            loop {
+                if i >= chars.len() {
+                    break; // unterminated: runs to EOF
+                }
+                if chars[i] == '\'' {
+                    if i + 1 < chars.len() && chars[i + 1] == '\'' {
+                        advance(&mut i, &mut line, &mut column, &chars);
+                        advance(&mut i, &mut line, &mut column, &chars);
+                        continue;
+                    }
+                    advance(&mut i, &mut line, &mut column, &chars);
+                    break;
+                }
+                advance(&mut i, &mut line, &mut column, &chars);
+            }
+            tokens.push(Token {
+                kind: TokenKind::StringLit,
+                text: chars[start..i].iter().collect(),
+                line: start_line,
+                column: start_col,
+            });
+            continue;
+        }
+
+        // Double-quoted identifier.
+        if c == '"' {
+            let start = i;
+            advance(&mut i, &mut line, &mut column, &chars);
+            while i < chars.len() && chars[i] != '"' {
+                advance(&mut i, &mut line, &mut column, &chars);
+            }
+            if i < chars.len() {
+                advance(&mut i, &mut line, &mut column, &chars);
+            }
+            tokens.push(Token {
+                kind: TokenKind::QuotedIdent,
+                text: chars[start..i].iter().collect(),
+                line: start_line,
+                column: start_col,
+            });
+            continue;
+        }
+
+        if c == ',' {
+            advance(&mut i, &mut line, &mut column, &chars);
+            tokens.push(Token {
+                kind: TokenKind::Comma,
+                text: ",".to_string(),
+                line: start_line,
+                column: start_col,
+            });
+            continue;
+        }
+
+        if c == '(' {
+            advance(&mut i, &mut line, &mut column, &chars);
+            tokens.push(Token {
+                kind: TokenKind::LParen,
+                text: "(".to_string(),
+                line: start_line,
+                column: start_col,
+            });
+            continue;
+        }
+
+        if c == ')' {
+            advance(&mut i, &mut line, &mut column, &chars);
+            tokens.push(Token {
+                kind: TokenKind::RParen,
+                text: ")".to_string(),
+                line: start_line,
+                column: start_col,
+            });
+            continue;
+        }
+
+        if c == ';' {
+            advance(&mut i, &mut line, &mut column, &chars);
+            tokens.push(Token {
+                kind: TokenKind::Semicolon,
+                text: ";".to_string(),
+                line: start_line,
+                column: start_col,
+            });
+            continue;
+        }
+
+        if "=<>!+-*/|%".contains(c) {
+            let start = i;
+            while i < chars.len() && "=<>!+-*/|%".contains(chars[i]) {
+                advance(&mut i, &mut line, &mut column, &chars);
+            }
+            tokens.push(Token {
+                kind: TokenKind::Operator,
+                text: chars[start..i].iter().collect(),
+                line: start_line,
+                column: start_col,
+            });
+            continue;
+        }
+
+        if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                advance(&mut i, &mut line, &mut column, &chars);
+            }
+            tokens.push(Token {
+                kind: TokenKind::Number,
+                text: chars[start..i].iter().collect(),
+                line: start_line,
+                column: start_col,
+            });
+            continue;
+        }
+
+        if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                advance(&mut i, &mut line, &mut column, &chars);
+            }
+            let text: String = chars[start..i].iter().collect();
+            let kind = if is_keyword(&text) {
+                TokenKind::Keyword
+            } else {
+                TokenKind::Ident
+            };
+            tokens.push(Token {
+                kind,
+                text,
+                line: start_line,
+                column: start_col,
+            });
+            continue;
+        }
+
+        // Anything else (e.g. `.`, `:`) passes through as a single-char token.
+        advance(&mut i, &mut line, &mut column, &chars);
+        tokens.push(Token {
+            kind: TokenKind::Other,
+            text: c.to_string(),
+            line: start_line,
+            column: start_col,
+        });
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn string_literal_is_not_split_on_internal_keywords() {
+        let tokens = tokenize("SELECT 'select from where' FROM t");
+        let lit = tokens
+            .iter()
+            .find(|t| t.kind == TokenKind::StringLit)
+            .expect("string literal token");
+        assert_eq!(lit.text, "'select from where'");
+    }
+
+    #[test]
+    fn doubled_quote_escape_stays_inside_the_literal() {
+        let tokens = tokenize("SET x = 'it''s fine'");
+        let lit = tokens
+            .iter()
+            .find(|t| t.kind == TokenKind::StringLit)
+            .expect("string literal token");
+        assert_eq!(lit.text, "'it''s fine'");
+    }
+
+    #[test]
+    fn unterminated_block_comment_runs_to_end_of_input() {
+        let tokens = tokenize("SELECT 1 /* oops");
+        let comment = tokens
+            .iter()
+            .find(|t| t.kind == TokenKind::BlockComment)
+            .expect("block comment token");
+        assert_eq!(comment.text, "/* oops");
+    }
+
+    #[test]
+    fn line_comment_does_not_uppercase_target() {
+        let tokens = tokenize("select 1 -- select from\n");
+        let comment = tokens
+            .iter()
+            .find(|t| t.kind == TokenKind::LineComment)
+            .expect("line comment token");
+        assert_eq!(comment.text, "-- select from");
+    }
+}
@@ -1,9 +1,20 @@
+mod ast;
+mod check;
+mod diagnostics;
+mod doc;
+mod emit;
+mod file_lines;
+mod lexer;
 mod options;
+mod parser;
+mod printer;
 mod sqlfmt;
 
 use anyhow::{Context, Result};
 use clap::Parser;
-use options::{IndentStyle, Options};
+use emit::{EmitMode, FileResult};
+use file_lines::FileLines;
+use options::{CommaStyle, IndentStyle, Options};
 use std::fs;
 use std::io::{self, Read};
 use std::path::PathBuf;
@@ -15,14 +26,23 @@ struct Cli {
     #[arg(value_name = "FILES", value_hint = clap::ValueHint::FilePath)]
     files: Vec<PathBuf>,
 
-    /// Write result back to the file(s)
+    /// Write result back to the file(s). Shorthand for `--emit files`
     #[arg(long)]
     write: bool,
 
-    /// Print diff of changes
+    /// Print diff of changes. Shorthand for `--emit diff`
     #[arg(long)]
     diff: bool,
 
+    /// Check that input is already formatted, without writing anything.
+    /// Exits non-zero if any file would change
+    #[arg(long)]
+    check: bool,
+
+    /// How to report formatting results
+    #[arg(long, value_enum, default_value_t = EmitMode::Stdout)]
+    emit: EmitMode,
+
     /// Maximum line width
     #[arg(long, default_value_t = 88)]
     line_width: usize,
@@ -34,6 +54,17 @@ struct Cli {
     /// Force uppercase SQL keywords (true/false)
     #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
     uppercase_keywords: bool,
+
+    /// Put commas at the start of the next line instead of the end of the
+    /// previous one when a list breaks across multiple lines
+    #[arg(long)]
+    leading_commas: bool,
+
+    /// Restrict formatting to these line ranges: `FILE:START-END`,
+    /// repeatable, or a single JSON array of `{"file", "range"}` objects.
+    /// Statements outside the given ranges are left byte-for-byte unchanged
+    #[arg(long = "file-lines", value_name = "FILE:START-END")]
+    file_lines: Vec<String>,
 }
 
 fn main() -> Result<()> {
@@ -45,41 +76,59 @@ fn main() -> Result<()> {
         _ => IndentStyle::Two,
     };
 
+    let comma_style = if cli.leading_commas {
+        CommaStyle::Leading
+    } else {
+        CommaStyle::Trailing
+    };
+
     let opts = Options {
         line_width: cli.line_width,
         indent: indent_style,
         uppercase_keywords: cli.uppercase_keywords,
+        comma_style,
     };
 
+    let file_lines = FileLines::parse(&cli.file_lines)?;
+
+    if cli.check {
+        if cli.files.is_empty() {
+            anyhow::bail!("--check requires at least one input file");
+        }
+        return check::run(&cli.files, &opts, &file_lines);
+    }
+
     if cli.files.is_empty() {
         let mut buffer = String::new();
         io::stdin().read_to_string(&mut buffer)?;
-        let formatted = sqlfmt::format_sql(&buffer, &opts)?;
+        let formatted = sqlfmt::format_sql_restricted(&buffer, &opts, None, &file_lines)?;
         print!("{}", formatted);
         return Ok(());
     }
 
+    // `--write`/`--diff` are kept as shorthands for the matching `--emit`
+    // mode so existing invocations keep working.
+    let mode = if cli.write {
+        EmitMode::Files
+    } else if cli.diff {
+        EmitMode::Diff
+    } else {
+        cli.emit
+    };
+
+    let mut results = Vec::with_capacity(cli.files.len());
     for path in cli.files.iter() {
         let input = fs::read_to_string(path)
             .with_context(|| format!("Failed to read file {}", path.display()))?;
-        let formatted = sqlfmt::format_sql(&input, &opts)?;
-        if cli.write {
-            fs::write(path, &formatted)
-                .with_context(|| format!("Failed to write file {}", path.display()))?;
-        } else if cli.diff {
-            let changes = similar::TextDiff::from_lines(&input, &formatted);
-            for change in changes.iter_all_changes() {
-                let sign = match change.tag() {
-                    similar::ChangeTag::Delete => "-",
-                    similar::ChangeTag::Insert => "+",
-                    similar::ChangeTag::Equal => " ",
-                };
-                print!("{}{}", sign, change);
-            }
-        } else {
-            print!("{}", formatted);
-        }
+        let formatted = sqlfmt::format_sql_restricted(&input, &opts, Some(path), &file_lines)?;
+        let diagnostics = diagnostics::analyze_variables(&input);
+        results.push(FileResult {
+            path: path.clone(),
+            original: input,
+            formatted,
+            diagnostics,
+        });
     }
 
-    Ok(())
+    emit::emit(mode, &results)
 }
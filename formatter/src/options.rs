@@ -4,11 +4,22 @@ pub enum IndentStyle {
     Four,
 }
 
+/// Where the comma goes when a list (`SELECT` projection, `IN (...)`, call
+/// arguments, ...) doesn't fit on one line and breaks to one item per line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommaStyle {
+    /// `a,\n  b,\n  c` - the comma trails the item it follows.
+    Trailing,
+    /// `a\n  , b\n  , c` - the comma leads the item it precedes.
+    Leading,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Options {
     pub line_width: usize,
     pub indent: IndentStyle,
     pub uppercase_keywords: bool,
+    pub comma_style: CommaStyle,
 }
 
 impl Options {
@@ -0,0 +1,639 @@
+//! Recursive-descent parser from a token stream into the [`crate::ast`].
+//!
+//! Statements are split on top-level `;` (i.e. not inside parens, strings,
+//! or comments) before parsing, so a parse error or unsupported
+//! construct in one statement can't corrupt its neighbours. Any statement
+//! that isn't a plain `SELECT`, or that carries a comment we don't have a
+//! layout rule for yet, is kept as [`Statement::Other`] rather than
+//! guessed at.
+
+use crate::ast::{Expr, FromItem, Join, SelectItem, SelectStmt, Statement};
+use crate::lexer::{Token, TokenKind};
+
+/// Splits a full token stream (including trivia) into per-statement token
+/// spans, breaking on `;` that isn't nested inside parentheses. The
+/// terminating semicolon stays attached to the statement it ends; any
+/// trivia after it starts the next statement.
+pub fn split_statements(tokens: Vec<Token>) -> Vec<Vec<Token>> {
+    let mut statements = Vec::new();
+    let mut current = Vec::new();
+    let mut depth = 0i32;
+
+    for tok in tokens {
+        match tok.kind {
+            TokenKind::LParen => depth += 1,
+            TokenKind::RParen => depth -= 1,
+            _ => {}
+        }
+        let is_terminator = tok.kind == TokenKind::Semicolon && depth <= 0;
+        current.push(tok);
+        if is_terminator {
+            statements.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        statements.push(current);
+    }
+    statements
+}
+
+/// Parses one statement's token span into an AST node, falling back to
+/// [`Statement::Other`] for anything that isn't a comment-free `SELECT`.
+pub fn parse_statement(tokens: Vec<Token>) -> Statement {
+    let has_comment = tokens
+        .iter()
+        .any(|t| matches!(t.kind, TokenKind::LineComment | TokenKind::BlockComment));
+    if has_comment {
+        return Statement::Other(tokens);
+    }
+
+    let significant: Vec<Token> = tokens
+        .iter()
+        .filter(|t| !t.is_trivia())
+        .cloned()
+        .collect();
+
+    let is_select = significant
+        .first()
+        .map(|t| t.kind == TokenKind::Keyword && t.text.eq_ignore_ascii_case("select"))
+        .unwrap_or(false);
+    if !is_select {
+        return Statement::Other(tokens);
+    }
+
+    let mut parser = Parser::new(significant);
+    match parser.parse_select() {
+        Some(select) if parser.at_end_or_semicolon() => Statement::Select(Box::new(select)),
+        _ => Statement::Other(tokens),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(tokens: Vec<Token>) -> Self {
+        Parser { tokens, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn is_kw(&self, kw: &str) -> bool {
+        self.peek()
+            .map(|t| t.kind == TokenKind::Keyword && t.text.eq_ignore_ascii_case(kw))
+            .unwrap_or(false)
+    }
+
+    fn eat_kw(&mut self, kw: &str) -> bool {
+        if self.is_kw(kw) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let tok = self.tokens.get(self.pos).cloned();
+        if tok.is_some() {
+            self.pos += 1;
+        }
+        tok
+    }
+
+    fn at_end_or_semicolon(&self) -> bool {
+        match self.peek() {
+            None => true,
+            Some(t) => t.kind == TokenKind::Semicolon && self.pos + 1 >= self.tokens.len(),
+        }
+    }
+
+    fn parse_select(&mut self) -> Option<SelectStmt> {
+        if !self.eat_kw("select") {
+            return None;
+        }
+        let distinct = self.eat_kw("distinct");
+        let _ = self.eat_kw("all");
+
+        let mut projection = Vec::new();
+        loop {
+            let expr = self.parse_expr()?;
+            let alias = self.parse_opt_alias();
+            projection.push(SelectItem { expr, alias });
+            if self.peek().map(|t| t.kind == TokenKind::Comma).unwrap_or(false) {
+                self.pos += 1;
+                continue;
+            }
+            break;
+        }
+
+        let mut from = Vec::new();
+        if self.eat_kw("from") {
+            from = self.parse_from_list()?;
+        }
+
+        let where_clause = if self.eat_kw("where") {
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+
+        let mut group_by = Vec::new();
+        if self.eat_kw("group") {
+            if !self.eat_kw("by") {
+                return None;
+            }
+            group_by = self.parse_expr_list()?;
+        }
+
+        let having = if self.eat_kw("having") {
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+
+        let mut order_by = Vec::new();
+        if self.eat_kw("order") {
+            if !self.eat_kw("by") {
+                return None;
+            }
+            order_by = self.parse_order_list()?;
+        }
+
+        let limit = if self.eat_kw("limit") {
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+        let offset = if self.eat_kw("offset") {
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+
+        Some(SelectStmt {
+            distinct,
+            projection,
+            from,
+            where_clause,
+            group_by,
+            having,
+            order_by,
+            limit,
+            offset,
+        })
+    }
+
+    fn parse_opt_alias(&mut self) -> Option<String> {
+        if self.eat_kw("as") {
+            return self.advance().map(|t| t.text);
+        }
+        if let Some(t) = self.peek() {
+            let is_ident = matches!(t.kind, TokenKind::Ident | TokenKind::QuotedIdent);
+            if is_ident {
+                // Only treat a bare identifier as an alias if it isn't the
+                // start of the next clause keyword (callers stop on those
+                // separately, so any Ident here is safe to consume).
+                let text = t.text.clone();
+                self.pos += 1;
+                return Some(text);
+            }
+        }
+        None
+    }
+
+    fn parse_expr_list(&mut self) -> Option<Vec<Expr>> {
+        let mut items = vec![self.parse_expr()?];
+        while self.peek().map(|t| t.kind == TokenKind::Comma).unwrap_or(false) {
+            self.pos += 1;
+            items.push(self.parse_expr()?);
+        }
+        Some(items)
+    }
+
+    fn parse_order_list(&mut self) -> Option<Vec<Expr>> {
+        let mut items = Vec::new();
+        loop {
+            let mut expr = self.parse_expr()?;
+            if self.eat_kw("asc") {
+                expr = Expr::BinOp {
+                    left: Box::new(expr),
+                    op: String::new(),
+                    right: Box::new(Expr::Raw("asc".to_string())),
+                };
+            } else if self.eat_kw("desc") {
+                expr = Expr::BinOp {
+                    left: Box::new(expr),
+                    op: String::new(),
+                    right: Box::new(Expr::Raw("desc".to_string())),
+                };
+            }
+            items.push(expr);
+            if self.peek().map(|t| t.kind == TokenKind::Comma).unwrap_or(false) {
+                self.pos += 1;
+                continue;
+            }
+            break;
+        }
+        Some(items)
+    }
+
+    fn parse_from_list(&mut self) -> Option<Vec<FromItem>> {
+        let mut items = vec![self.parse_from_item()?];
+        while self.peek().map(|t| t.kind == TokenKind::Comma).unwrap_or(false) {
+            self.pos += 1;
+            items.push(self.parse_from_item()?);
+        }
+        Some(items)
+    }
+
+    fn parse_from_item(&mut self) -> Option<FromItem> {
+        let expr = self.parse_primary()?;
+        let alias = self.parse_opt_alias_guarded();
+        let mut joins = Vec::new();
+        while let Some(kind) = self.peek_join_kind() {
+            for _ in 0..kind.1 {
+                self.pos += 1;
+            }
+            let table = self.parse_primary()?;
+            let join_alias = self.parse_opt_alias_guarded();
+            let on = if self.eat_kw("on") {
+                Some(self.parse_expr()?)
+            } else {
+                None
+            };
+            joins.push(Join {
+                kind: kind.0,
+                table,
+                alias: join_alias,
+                on,
+            });
+        }
+        Some(FromItem { expr, alias, joins })
+    }
+
+    /// Same rule as [`Self::parse_opt_alias`]: only an identifier is taken
+    /// as a bare alias, so a clause keyword (`WHERE`, `JOIN`, ...) is never
+    /// swallowed as one.
+    fn parse_opt_alias_guarded(&mut self) -> Option<String> {
+        if self.eat_kw("as") {
+            return self.advance().map(|t| t.text);
+        }
+        if let Some(t) = self.peek() {
+            if matches!(t.kind, TokenKind::Ident | TokenKind::QuotedIdent) {
+                let text = t.text.clone();
+                self.pos += 1;
+                return Some(text);
+            }
+        }
+        None
+    }
+
+    fn peek_join_kind(&self) -> Option<(String, usize)> {
+        if self.is_kw("join") {
+            return Some(("JOIN".to_string(), 1));
+        }
+        let (prefix, count) = if self.is_kw("inner") {
+            ("INNER", 1)
+        } else if self.is_kw("left") {
+            ("LEFT", 1)
+        } else if self.is_kw("right") {
+            ("RIGHT", 1)
+        } else if self.is_kw("full") {
+            ("FULL", 1)
+        } else {
+            return None;
+        };
+        let mut offset = count;
+        let mut label = prefix.to_string();
+        if self
+            .tokens
+            .get(self.pos + offset)
+            .map(|t| t.kind == TokenKind::Keyword && t.text.eq_ignore_ascii_case("outer"))
+            .unwrap_or(false)
+        {
+            label.push_str(" OUTER");
+            offset += 1;
+        }
+        if self
+            .tokens
+            .get(self.pos + offset)
+            .map(|t| t.kind == TokenKind::Keyword && t.text.eq_ignore_ascii_case("join"))
+            .unwrap_or(false)
+        {
+            label.push_str(" JOIN");
+            offset += 1;
+            Some((label, offset))
+        } else {
+            None
+        }
+    }
+
+    fn parse_expr(&mut self) -> Option<Expr> {
+        self.parse_or()
+    }
+
+    fn parse_or(&mut self) -> Option<Expr> {
+        let mut left = self.parse_and()?;
+        while self.eat_kw("or") {
+            let right = self.parse_and()?;
+            left = Expr::BinOp {
+                left: Box::new(left),
+                op: "OR".to_string(),
+                right: Box::new(right),
+            };
+        }
+        Some(left)
+    }
+
+    fn parse_and(&mut self) -> Option<Expr> {
+        let mut left = self.parse_not()?;
+        while self.eat_kw("and") {
+            let right = self.parse_not()?;
+            left = Expr::BinOp {
+                left: Box::new(left),
+                op: "AND".to_string(),
+                right: Box::new(right),
+            };
+        }
+        Some(left)
+    }
+
+    fn parse_not(&mut self) -> Option<Expr> {
+        if self.eat_kw("not") {
+            return Some(Expr::Not(Box::new(self.parse_not()?)));
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Option<Expr> {
+        let left = self.parse_additive()?;
+
+        if self.eat_kw("in") {
+            return self.parse_in_tail(left, false);
+        }
+        if self.is_kw("not")
+            && self
+                .tokens
+                .get(self.pos + 1)
+                .map(|t| t.kind == TokenKind::Keyword && t.text.eq_ignore_ascii_case("in"))
+                .unwrap_or(false)
+        {
+            self.pos += 2;
+            return self.parse_in_tail(left, true);
+        }
+        if self.eat_kw("is") {
+            let negated = self.eat_kw("not");
+            let _ = self.eat_kw("null");
+            let label = if negated { "is not null" } else { "is null" };
+            return Some(Expr::BinOp {
+                left: Box::new(left),
+                op: String::new(),
+                right: Box::new(Expr::Raw(label.to_string())),
+            });
+        }
+
+        if let Some(t) = self.peek() {
+            if t.kind == TokenKind::Operator {
+                let op = t.text.clone();
+                self.pos += 1;
+                let right = self.parse_additive()?;
+                return Some(Expr::BinOp {
+                    left: Box::new(left),
+                    op,
+                    right: Box::new(right),
+                });
+            }
+        }
+        Some(left)
+    }
+
+    fn parse_in_tail(&mut self, left: Expr, negated: bool) -> Option<Expr> {
+        if self.peek().map(|t| t.kind == TokenKind::LParen).unwrap_or(false) {
+            self.pos += 1;
+            if self.is_kw("select") {
+                let sub = self.parse_select()?;
+                if self.peek().map(|t| t.kind == TokenKind::RParen).unwrap_or(false) {
+                    self.pos += 1;
+                }
+                return Some(Expr::In {
+                    expr: Box::new(left),
+                    negated,
+                    list: vec![Expr::SubQuery(Box::new(sub))],
+                });
+            }
+            let list = self.parse_expr_list()?;
+            if self.peek().map(|t| t.kind == TokenKind::RParen).unwrap_or(false) {
+                self.pos += 1;
+            }
+            Some(Expr::In {
+                expr: Box::new(left),
+                negated,
+                list,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn parse_additive(&mut self) -> Option<Expr> {
+        let mut left = self.parse_multiplicative()?;
+        while let Some(t) = self.peek() {
+            if t.kind == TokenKind::Operator && (t.text == "+" || t.text == "-") {
+                let op = t.text.clone();
+                self.pos += 1;
+                let right = self.parse_multiplicative()?;
+                left = Expr::BinOp {
+                    left: Box::new(left),
+                    op,
+                    right: Box::new(right),
+                };
+            } else {
+                break;
+            }
+        }
+        Some(left)
+    }
+
+    fn parse_multiplicative(&mut self) -> Option<Expr> {
+        let mut left = self.parse_primary()?;
+        while let Some(t) = self.peek() {
+            if t.kind == TokenKind::Operator && (t.text == "*" || t.text == "/") {
+                let op = t.text.clone();
+                self.pos += 1;
+                let right = self.parse_primary()?;
+                left = Expr::BinOp {
+                    left: Box::new(left),
+                    op,
+                    right: Box::new(right),
+                };
+            } else {
+                break;
+            }
+        }
+        Some(left)
+    }
+
+    fn parse_primary(&mut self) -> Option<Expr> {
+        let t = self.peek()?.clone();
+        match t.kind {
+            TokenKind::Operator if t.text == "*" => {
+                // A bare `*` where an operand is expected (`SELECT *`,
+                // `COUNT(*)`), not a multiplication - `parse_multiplicative`
+                // consumes a multiplying `*` as an operator before it gets
+                // here.
+                self.pos += 1;
+                Some(Expr::Ident("*".to_string()))
+            }
+            TokenKind::Operator if t.text == "-" || t.text == "+" => {
+                self.pos += 1;
+                let expr = self.parse_primary()?;
+                Some(Expr::Unary {
+                    op: t.text,
+                    expr: Box::new(expr),
+                })
+            }
+            TokenKind::Number => {
+                self.pos += 1;
+                Some(Expr::Number(t.text))
+            }
+            TokenKind::StringLit => {
+                self.pos += 1;
+                Some(Expr::StringLit(t.text))
+            }
+            TokenKind::QuotedIdent => {
+                self.pos += 1;
+                Some(Expr::QuotedIdent(t.text))
+            }
+            TokenKind::LParen => {
+                self.pos += 1;
+                if self.is_kw("select") {
+                    let sub = self.parse_select()?;
+                    if self.peek().map(|t| t.kind == TokenKind::RParen).unwrap_or(false) {
+                        self.pos += 1;
+                    }
+                    Some(Expr::SubQuery(Box::new(sub)))
+                } else {
+                    let inner = self.parse_expr()?;
+                    if self.peek().map(|t| t.kind == TokenKind::RParen).unwrap_or(false) {
+                        self.pos += 1;
+                    }
+                    Some(Expr::Paren(Box::new(inner)))
+                }
+            }
+            TokenKind::Keyword if t.text.eq_ignore_ascii_case("case") => self.parse_case(),
+            TokenKind::Keyword if t.text.eq_ignore_ascii_case("exists") => {
+                self.pos += 1;
+                let inner = self.parse_primary()?;
+                Some(Expr::Call {
+                    name: "EXISTS".to_string(),
+                    args: vec![inner],
+                })
+            }
+            TokenKind::Ident | TokenKind::Keyword => {
+                self.pos += 1;
+                let mut name = t.text.clone();
+                // Qualified name: `a.b.c`.
+                while self
+                    .peek()
+                    .map(|t| t.kind == TokenKind::Other && t.text == ".")
+                    .unwrap_or(false)
+                {
+                    self.pos += 1;
+                    if let Some(next) = self.peek().cloned() {
+                        if matches!(next.kind, TokenKind::Ident | TokenKind::Keyword) {
+                            name.push('.');
+                            name.push_str(&next.text);
+                            self.pos += 1;
+                        } else if next.kind == TokenKind::Operator && next.text == "*" {
+                            // A qualified wildcard, `t.*`.
+                            name.push_str(".*");
+                            self.pos += 1;
+                        }
+                    }
+                }
+                if self.peek().map(|t| t.kind == TokenKind::LParen).unwrap_or(false) {
+                    self.pos += 1;
+                    let mut args = Vec::new();
+                    if !self.peek().map(|t| t.kind == TokenKind::RParen).unwrap_or(true) {
+                        args = self.parse_expr_list()?;
+                    }
+                    if self.peek().map(|t| t.kind == TokenKind::RParen).unwrap_or(false) {
+                        self.pos += 1;
+                    }
+                    return Some(Expr::Call { name, args });
+                }
+                Some(Expr::Ident(name))
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_case(&mut self) -> Option<Expr> {
+        self.pos += 1; // consume CASE
+        let operand = if !self.is_kw("when") {
+            Some(Box::new(self.parse_expr()?))
+        } else {
+            None
+        };
+        let mut whens = Vec::new();
+        while self.eat_kw("when") {
+            let cond = self.parse_expr()?;
+            if !self.eat_kw("then") {
+                return None;
+            }
+            let result = self.parse_expr()?;
+            whens.push((cond, result));
+        }
+        if whens.is_empty() {
+            return None;
+        }
+        let else_branch = if self.eat_kw("else") {
+            Some(Box::new(self.parse_expr()?))
+        } else {
+            None
+        };
+        if !self.eat_kw("end") {
+            return None;
+        }
+        Some(Expr::Case {
+            operand,
+            whens,
+            else_branch,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer;
+
+    #[test]
+    fn bare_star_projection_parses_as_select() {
+        let tokens = lexer::tokenize("SELECT * FROM accounts;");
+        assert!(matches!(parse_statement(tokens), Statement::Select(_)));
+    }
+
+    #[test]
+    fn qualified_star_projection_parses_as_select() {
+        let tokens = lexer::tokenize("SELECT t.* FROM accounts t;");
+        assert!(matches!(parse_statement(tokens), Statement::Select(_)));
+    }
+
+    #[test]
+    fn unary_minus_in_where_clause_parses_as_select() {
+        let tokens = lexer::tokenize("SELECT id FROM t WHERE id = -1;");
+        assert!(matches!(parse_statement(tokens), Statement::Select(_)));
+    }
+
+    #[test]
+    fn unary_plus_is_also_accepted() {
+        let tokens = lexer::tokenize("SELECT id FROM t WHERE id = +1;");
+        assert!(matches!(parse_statement(tokens), Statement::Select(_)));
+    }
+}
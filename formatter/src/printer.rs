@@ -0,0 +1,314 @@
+//! Lowers the [`crate::ast`] into a [`crate::doc::Doc`] for layout.
+//!
+//! Each clause (`SELECT`, `FROM`, `WHERE`, `CASE`/`WHEN`/`END`, parenthesized
+//! subqueries) is wrapped in its own `group`, so a short query collapses to
+//! one line while a long one breaks clause-by-clause with consistent
+//! indentation, and an overflowing list inside a clause can break
+//! independently of the clauses around it.
+
+use crate::ast::{Expr, FromItem, Join, SelectItem, SelectStmt};
+use crate::doc::{concat, group, join, line, nest, softline, text, Doc};
+use crate::lexer;
+use crate::options::{CommaStyle, Options};
+
+/// Applies keyword casing to a bare word (or each `.`-separated segment of
+/// a qualified name) so a keyword used as an expression atom - a function
+/// name like `MAX`, or the `NULL` literal - is cased the same as a keyword
+/// appearing in clause position.
+fn cased_name(name: &str, opts: &Options) -> String {
+    name.split('.')
+        .map(|segment| {
+            if opts.uppercase_keywords && lexer::is_keyword(segment) {
+                segment.to_ascii_uppercase()
+            } else {
+                segment.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+pub fn print_select(stmt: &SelectStmt, opts: &Options) -> Doc {
+    let indent = opts.indent_width();
+    let mut clauses = vec![print_select_clause(stmt, opts)];
+
+    if !stmt.from.is_empty() {
+        clauses.push(print_from_clause(&stmt.from, opts));
+    }
+    if let Some(w) = &stmt.where_clause {
+        clauses.push(group(concat(vec![
+            text("WHERE"),
+            nest(indent, concat(vec![line(), print_expr(w, opts)])),
+        ])));
+    }
+    if !stmt.group_by.is_empty() {
+        let items = stmt.group_by.iter().map(|e| print_expr(e, opts)).collect();
+        clauses.push(group(concat(vec![
+            text("GROUP BY"),
+            nest(indent, concat(vec![line(), list_doc(items, opts)])),
+        ])));
+    }
+    if let Some(h) = &stmt.having {
+        clauses.push(group(concat(vec![
+            text("HAVING"),
+            nest(indent, concat(vec![line(), print_expr(h, opts)])),
+        ])));
+    }
+    if !stmt.order_by.is_empty() {
+        let items = stmt.order_by.iter().map(|e| print_expr(e, opts)).collect();
+        clauses.push(group(concat(vec![
+            text("ORDER BY"),
+            nest(indent, concat(vec![line(), list_doc(items, opts)])),
+        ])));
+    }
+    if let Some(l) = &stmt.limit {
+        clauses.push(concat(vec![text("LIMIT "), print_expr(l, opts)]));
+    }
+    if let Some(o) = &stmt.offset {
+        clauses.push(concat(vec![text("OFFSET "), print_expr(o, opts)]));
+    }
+
+    group(join(line(), clauses))
+}
+
+fn print_select_clause(stmt: &SelectStmt, opts: &Options) -> Doc {
+    let indent = opts.indent_width();
+    let kw = if stmt.distinct { "SELECT DISTINCT" } else { "SELECT" };
+    let items: Vec<Doc> = stmt
+        .projection
+        .iter()
+        .map(|item| print_select_item(item, opts))
+        .collect();
+    group(concat(vec![
+        text(kw),
+        nest(indent, concat(vec![line(), list_doc(items, opts)])),
+    ]))
+}
+
+fn print_select_item(item: &SelectItem, opts: &Options) -> Doc {
+    match &item.alias {
+        Some(alias) => concat(vec![print_expr(&item.expr, opts), text(" AS "), text(alias.clone())]),
+        None => print_expr(&item.expr, opts),
+    }
+}
+
+fn print_from_clause(items: &[FromItem], opts: &Options) -> Doc {
+    let indent = opts.indent_width();
+    let docs: Vec<Doc> = items.iter().map(|item| print_from_item(item, opts)).collect();
+    group(concat(vec![
+        text("FROM"),
+        nest(
+            indent,
+            concat(vec![line(), join(concat(vec![text(","), line()]), docs)]),
+        ),
+    ]))
+}
+
+fn print_from_item(item: &FromItem, opts: &Options) -> Doc {
+    let indent = opts.indent_width();
+    let base = match &item.alias {
+        Some(alias) => concat(vec![print_expr(&item.expr, opts), text(" "), text(alias.clone())]),
+        None => print_expr(&item.expr, opts),
+    };
+    if item.joins.is_empty() {
+        return base;
+    }
+    let join_docs: Vec<Doc> = item.joins.iter().map(|j| print_join(j, opts)).collect();
+    concat(vec![
+        base,
+        nest(indent, concat(vec![line(), join(line(), join_docs)])),
+    ])
+}
+
+fn print_join(join_clause: &Join, opts: &Options) -> Doc {
+    let indent = opts.indent_width();
+    let table = match &join_clause.alias {
+        Some(alias) => concat(vec![print_expr(&join_clause.table, opts), text(" "), text(alias.clone())]),
+        None => print_expr(&join_clause.table, opts),
+    };
+    let mut parts = vec![text(format!("{} ", join_clause.kind)), table];
+    if let Some(on) = &join_clause.on {
+        parts.push(nest(indent, concat(vec![line(), text("ON "), print_expr(on, opts)])));
+    }
+    group(concat(parts))
+}
+
+/// Joins `items` with the comma placement from `Options::comma_style`, but
+/// does not wrap the result in its own `group` - callers that need the
+/// list to break independently of its siblings should wrap the result
+/// themselves (see [`list_doc`] and the parenthesized-list sites below).
+fn list_items(items: Vec<Doc>, opts: &Options) -> Doc {
+    match opts.comma_style {
+        CommaStyle::Trailing => join(concat(vec![text(","), line()]), items),
+        CommaStyle::Leading => {
+            let mut parts = Vec::new();
+            for (i, item) in items.into_iter().enumerate() {
+                if i > 0 {
+                    parts.push(softline());
+                    parts.push(text(", "));
+                }
+                parts.push(item);
+            }
+            concat(parts)
+        }
+    }
+}
+
+/// Renders a comma-separated list: flat on one line if it fits, otherwise
+/// one element per line at the current indent, per `Options::comma_style`.
+pub fn list_doc(items: Vec<Doc>, opts: &Options) -> Doc {
+    group(list_items(items, opts))
+}
+
+/// Renders a parenthesized, comma-separated list (call arguments, `IN`
+/// lists): no padding space against the parens when flat, one item per
+/// line indented under the opening paren when broken.
+fn paren_list_doc(items: Vec<Doc>, opts: &Options) -> Doc {
+    group(concat(vec![
+        nest(2, concat(vec![softline(), list_items(items, opts)])),
+        softline(),
+    ]))
+}
+
+pub fn print_expr(expr: &Expr, opts: &Options) -> Doc {
+    match expr {
+        Expr::Ident(s) => text(cased_name(s, opts)),
+        Expr::QuotedIdent(s) => text(s.clone()),
+        Expr::Number(s) => text(s.clone()),
+        Expr::StringLit(s) => text(s.clone()),
+        Expr::BinOp { left, op, right } => {
+            if op.is_empty() {
+                // Synthetic markers (IS NULL, ASC, DESC, ...) attach with a
+                // single separating space.
+                concat(vec![print_expr(left, opts), text(" "), print_expr(right, opts)])
+            } else {
+                concat(vec![
+                    print_expr(left, opts),
+                    text(format!(" {} ", op)),
+                    print_expr(right, opts),
+                ])
+            }
+        }
+        Expr::Not(inner) => concat(vec![text("NOT "), print_expr(inner, opts)]),
+        Expr::Unary { op, expr } => concat(vec![text(op.clone()), print_expr(expr, opts)]),
+        Expr::Call { name, args } => {
+            let arg_docs: Vec<Doc> = args.iter().map(|a| print_expr(a, opts)).collect();
+            concat(vec![
+                text(format!("{}(", cased_name(name, opts))),
+                paren_list_doc(arg_docs, opts),
+                text(")"),
+            ])
+        }
+        Expr::Case {
+            operand,
+            whens,
+            else_branch,
+        } => print_case(operand, whens, else_branch, opts),
+        Expr::In { expr, negated, list } => {
+            let kw = if *negated { "NOT IN" } else { "IN" };
+            let list_docs: Vec<Doc> = list.iter().map(|e| print_expr(e, opts)).collect();
+            concat(vec![
+                print_expr(expr, opts),
+                text(format!(" {} (", kw)),
+                paren_list_doc(list_docs, opts),
+                text(")"),
+            ])
+        }
+        Expr::Paren(inner) => concat(vec![text("("), print_expr(inner, opts), text(")")]),
+        Expr::SubQuery(select) => concat(vec![text("("), print_select(select, opts), text(")")]),
+        Expr::Raw(s) => text(
+            s.split(' ')
+                .map(|word| cased_name(word, opts))
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+    }
+}
+
+fn print_case(
+    operand: &Option<Box<Expr>>,
+    whens: &[(Expr, Expr)],
+    else_branch: &Option<Box<Expr>>,
+    opts: &Options,
+) -> Doc {
+    let mut body = Vec::new();
+    for (cond, result) in whens {
+        body.push(line());
+        body.push(concat(vec![
+            text("WHEN "),
+            print_expr(cond, opts),
+            text(" THEN "),
+            print_expr(result, opts),
+        ]));
+    }
+    if let Some(e) = else_branch {
+        body.push(line());
+        body.push(concat(vec![text("ELSE "), print_expr(e, opts)]));
+    }
+    let head = match operand {
+        Some(o) => concat(vec![text("CASE "), print_expr(o, opts)]),
+        None => text("CASE"),
+    };
+    group(concat(vec![head, nest(2, concat(body)), line(), text("END")]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc::render;
+    use crate::options::IndentStyle;
+
+    fn opts(comma_style: CommaStyle) -> Options {
+        Options {
+            line_width: 20,
+            indent: IndentStyle::Two,
+            uppercase_keywords: true,
+            comma_style,
+        }
+    }
+
+    #[test]
+    fn trailing_commas_break_one_item_per_line() {
+        let items = vec![text("aaaaaaaa"), text("bbbbbbbb"), text("cccccccc")];
+        let doc = list_doc(items, &opts(CommaStyle::Trailing));
+        assert_eq!(
+            render(&doc, 20),
+            "aaaaaaaa,\nbbbbbbbb,\ncccccccc"
+        );
+    }
+
+    #[test]
+    fn leading_commas_put_the_comma_before_the_next_item() {
+        let items = vec![text("aaaaaaaa"), text("bbbbbbbb"), text("cccccccc")];
+        let doc = list_doc(items, &opts(CommaStyle::Leading));
+        assert_eq!(
+            render(&doc, 20),
+            "aaaaaaaa\n, bbbbbbbb\n, cccccccc"
+        );
+    }
+
+    #[test]
+    fn nested_call_args_do_not_split_at_the_outer_commas() {
+        // COALESCE(a, b) as a single projection item must stay intact even
+        // though the SELECT clause around it breaks.
+        let stmt = crate::ast::SelectStmt {
+            distinct: false,
+            projection: vec![SelectItem {
+                expr: Expr::Call {
+                    name: "COALESCE".to_string(),
+                    args: vec![Expr::Ident("a".to_string()), Expr::Ident("b".to_string())],
+                },
+                alias: None,
+            }],
+            from: vec![],
+            where_clause: None,
+            group_by: vec![],
+            having: None,
+            order_by: vec![],
+            limit: None,
+            offset: None,
+        };
+        let doc = print_select(&stmt, &opts(CommaStyle::Trailing));
+        assert_eq!(render(&doc, 20), "SELECT\n  COALESCE(a, b)");
+    }
+}
@@ -1,209 +1,385 @@
+//! Entry point for formatting: tokenize, split into statements, parse the
+//! ones we understand deeply (`SELECT`) into an AST and lay them out with
+//! the [`crate::doc`] pretty-printer, and pass everything else through a
+//! token-aware renderer that still normalizes keyword casing and operator
+//! spacing without touching string/comment bytes.
+
+use std::path::Path;
+
 use anyhow::Result;
-use regex::Regex;
 
+use crate::ast::Statement;
+use crate::doc;
+use crate::file_lines::FileLines;
+use crate::lexer::{self, Token, TokenKind};
 use crate::options::Options;
+use crate::parser;
+use crate::printer;
 
-const KEYWORDS: &[&str] = &[
-    "select",
-    "insert",
-    "update",
-    "delete",
-    "from",
-    "where",
-    "group",
-    "by",
-    "order",
-    "having",
-    "limit",
-    "offset",
-    "join",
-    "inner",
-    "left",
-    "right",
-    "full",
-    "outer",
-    "on",
-    "as",
-    "and",
-    "or",
-    "not",
-    "null",
-    "is",
-    "in",
-    "exists",
-    "case",
-    "when",
-    "then",
-    "else",
-    "end",
-    "create",
-    "table",
-    "view",
-    "function",
-    "procedure",
-    "if",
-    "begin",
-    "commit",
-    "rollback",
-    "union",
-    "all",
-    "distinct",
-    "with",
-    "over",
-    "write",
-    "partition",
-    "into",
-    "values",
-    "return",
-    "returns",
-    "declare",
-    "set",
-    "local",
-    "real",
-    "integer",
-    "function",
-    "set",
-    "write",
-    "record",
-    "do",
-    "char",
-    "abs",
-    "max",
-    "min",
-    "timestamp",
-    "update",
-];
+/// Formats `input`, but only reformats statements whose original line span
+/// overlaps a range `file_lines` allows for `path`; everything else is
+/// emitted byte-for-byte, including its original keyword casing and
+/// operator spacing. Pass `path: None` with `file_lines: &FileLines::default()`
+/// to format everything, ignoring any `--file-lines` restriction.
+pub fn format_sql_restricted(
+    input: &str,
+    opts: &Options,
+    path: Option<&Path>,
+    file_lines: &FileLines,
+) -> Result<String> {
+    let tokens = lexer::tokenize(input);
+    let statements = parser::split_statements(tokens);
+
+    // `BEGIN`/`CASE`...`END` nesting depth, carried across statements since
+    // each semicolon-delimited statement is formatted independently but a
+    // block's body is made of several such statements.
+    let mut level = 0usize;
+    let mut out = String::new();
+    for (i, stmt_tokens) in statements.into_iter().enumerate() {
+        let (start_line, end_line) = statement_span(&stmt_tokens);
+        if file_lines.should_format(path, start_line, end_line) {
+            // A formatted statement drops its leading whitespace, so it
+            // starts on a line of its own.
+            if i > 0 {
+                out.push('\n');
+            }
+            out.push_str(&format_statement(stmt_tokens, opts, &mut level));
+        } else {
+            // Not reformatted, but its `BEGIN`/`CASE`/`END` keywords still
+            // count toward the depth the *next* formatted statement nests at.
+            adjust_nesting(&stmt_tokens, &mut level);
+            out.push_str(&verbatim(&stmt_tokens));
+        }
+    }
+
+    Ok(out.trim_end().to_string())
+}
 
-fn build_keyword_regex() -> Regex {
-    let pattern = KEYWORDS
+/// The first and last source line a statement's *significant* tokens cover,
+/// accounting for tokens (block comments, string literals) that embed
+/// newlines. Leading/trailing whitespace is ignored, since `split_statements`
+/// attaches a trailing blank line to whichever statement follows it, and
+/// that blank line shouldn't pull a neighbouring statement into range.
+fn statement_span(tokens: &[Token]) -> (usize, usize) {
+    let significant: Vec<&Token> = tokens
         .iter()
-        .map(|k| regex::escape(k))
-        .collect::<Vec<_>>()
-        .join("|");
-    Regex::new(&format!(r"(?i)\b(?:{})\b", pattern)).unwrap()
+        .filter(|t| t.kind != TokenKind::Whitespace)
+        .collect();
+    let spanned = if significant.is_empty() { tokens.iter().collect() } else { significant };
+
+    let start = spanned.first().map(|t| t.line).unwrap_or(1);
+    let end = spanned
+        .iter()
+        .map(|t| t.line + t.text.matches('\n').count())
+        .max()
+        .unwrap_or(start);
+    (start, end)
 }
 
-pub fn format_sql(input: &str, opts: &Options) -> Result<String> {
-    let kw_re = build_keyword_regex();
-    let mut s = input.to_string();
-
-    if opts.uppercase_keywords {
-        s = kw_re
-            .replace_all(&s, |caps: &regex::Captures| caps[0].to_ascii_uppercase())
-            .to_string();
-    }
-
-    // Normalize whitespace and indentation
-    // - Collapse multiple spaces
-    // - Ensure single space after commas and around operators
-    // - Break lines on common clause boundaries when exceeding line width (basic heuristic)
-
-    let mut lines = vec![];
-    for raw_line in s.lines() {
-        let mut line = raw_line.trim().to_string();
-
-        // Space after comma
-        line = Regex::new(r",\s*")
-            .unwrap()
-            .replace_all(&line, ", ")
-            .into_owned();
-        // Space around equals and comparison operators
-        line = Regex::new(r"\s*([=<>!]+)\s*")
-            .unwrap()
-            .replace_all(&line, " $1 ")
-            .into_owned();
-
-        // Naive wrapping at clause boundaries
-        let clause_breaks = [
-            " SELECT ",
-            " FROM ",
-            " WHERE ",
-            " GROUP BY ",
-            " ORDER BY ",
-            " HAVING ",
-            " LIMIT ",
-            " OFFSET ",
-            " JOIN ",
-            " INNER JOIN ",
-            " LEFT JOIN ",
-        ];
-        if line.len() > opts.line_width {
-            let mut out = String::new();
-            let mut rest = line.clone();
-            let indent = " ".repeat(opts.indent_width());
-            let mut first = true;
-            while rest.len() > opts.line_width {
-                let mut split_at = None;
-                for marker in &clause_breaks {
-                    if let Some(pos) = rest.find(marker) {
-                        if pos > 0 && pos < opts.line_width {
-                            split_at = Some(pos);
-                            break;
-                        }
-                    }
+/// Reproduces a statement's original source exactly, with no casing or
+/// spacing normalization, for statements a `--file-lines` restriction
+/// excludes from this run.
+fn verbatim(tokens: &[Token]) -> String {
+    tokens.iter().map(|t| t.text.as_str()).collect()
+}
+
+fn format_statement(tokens: Vec<Token>, opts: &Options, level: &mut usize) -> String {
+    match parser::parse_statement(tokens) {
+        Statement::Select(select) => {
+            let doc = printer::print_select(&select, opts);
+            let mut rendered = doc::render(&doc, opts.line_width);
+            rendered.push(';');
+            indent_block(&rendered, *level, opts)
+        }
+        Statement::Other(tokens) => render_tokens(&tokens, opts, level),
+    }
+}
+
+/// Renders a statement we don't have a deep grammar for: keyword casing and
+/// whitespace/operator normalization still apply, but only outside string
+/// literals, quoted identifiers, and comments, which are copied through
+/// byte-for-byte. Since there's no AST for `BEGIN...END`/`CASE...END`
+/// blocks, nesting is still tracked by keyword the way the old regex
+/// pipeline did: a line opening with `BEGIN` or `CASE` indents everything
+/// after it one level deeper, and a line opening with `END` dedents first.
+/// Every non-`SELECT` statement (`UPDATE`/`INSERT`/`DELETE`/`DECLARE`/...)
+/// goes through this path and never gets an AST, so a line that overflows
+/// `opts.line_width` is also wrapped here, the same naive clause-break way
+/// the old regex pipeline did it (see [`wrap_overlong_line`]).
+fn render_tokens(tokens: &[Token], opts: &Options, level: &mut usize) -> String {
+    let mut out = String::new();
+
+    for tok in tokens {
+        match tok.kind {
+            TokenKind::Keyword => {
+                if opts.uppercase_keywords {
+                    out.push_str(&tok.text.to_ascii_uppercase());
+                } else {
+                    out.push_str(&tok.text);
                 }
-                if split_at.is_none() {
-                    // fallback: split at last comma before limit
-                    if let Some(pos) = rest[..opts.line_width].rfind(',') {
-                        split_at = Some(pos + 1);
+            }
+            TokenKind::Whitespace => {
+                let newlines = tok.text.matches('\n').count();
+                if newlines > 0 {
+                    for _ in 0..newlines.min(2) {
+                        out.push('\n');
                     }
-                }
-                let idx = split_at.unwrap_or(opts.line_width);
-                let (head, tail) = rest.split_at(idx);
-                if first {
-                    out.push_str(head.trim_end());
-                    out.push('\n');
-                    first = false;
                 } else {
-                    out.push_str(&indent);
-                    out.push_str(head.trim());
-                    out.push('\n');
+                    push_space(&mut out);
                 }
-                rest = tail.trim().to_string();
             }
-            if !rest.is_empty() {
-                if !first {
-                    out.push_str(&indent);
-                }
-                out.push_str(&rest);
+            TokenKind::Comma => {
+                out.push(',');
+                push_space(&mut out);
             }
-            line = out;
+            TokenKind::Operator if tok.text.chars().all(|c| "=<>!".contains(c)) => {
+                push_space(&mut out);
+                out.push_str(&tok.text);
+                push_space(&mut out);
+            }
+            _ => out.push_str(&tok.text),
         }
+    }
 
-        lines.push(line);
+    let mut lines: Vec<String> = Vec::new();
+    for line in out.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            lines.push(String::new());
+            continue;
+        }
+        let upper = trimmed.to_ascii_uppercase();
+        if starts_with_word(&upper, "END") {
+            *level = level.saturating_sub(1);
+        }
+        let indent = " ".repeat(*level * opts.indent_width());
+        let full = format!("{}{}", indent, trimmed);
+        if starts_with_word(&upper, "BEGIN") || starts_with_word(&upper, "CASE") {
+            *level += 1;
+        }
+        lines.extend(wrap_overlong_line(&full, &indent, opts));
     }
 
-    // Basic indentation based on parentheses and block keywords
-    let mut indented = String::new();
-    let mut level = 0usize;
-    for mut line in lines {
-        let upper = line.to_ascii_uppercase();
-        let trimmed = upper.trim_start();
-        // decrease indent for END or closing paren
-        if trimmed.starts_with("END") || trimmed.starts_with(")") {
-            if level > 0 {
-                level -= 1;
+    // Trim wholly-blank lines from either end (mirroring the old
+    // whole-string `.trim()`), but line-wise so a leading indent on the
+    // first real line of content survives.
+    while lines.first().is_some_and(|l| l.is_empty()) {
+        lines.remove(0);
+    }
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+/// Applies a uniform indent to every non-empty line of already-rendered
+/// text, so a `SELECT` nested inside a `BEGIN...END` block lines up under
+/// it instead of resetting to column 0.
+fn indent_block(text: &str, level: usize, opts: &Options) -> String {
+    if level == 0 {
+        return text.to_string();
+    }
+    let indent = " ".repeat(level * opts.indent_width());
+    text.lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("{}{}", indent, line)
             }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Keeps the running `BEGIN`/`CASE`/`END` nesting depth in sync with a
+/// statement that was skipped (by `--file-lines`) rather than rendered, so
+/// the next formatted statement still nests at the right depth.
+fn adjust_nesting(tokens: &[Token], level: &mut usize) {
+    for tok in tokens {
+        if tok.kind != TokenKind::Keyword {
+            continue;
         }
-        let indent = " ".repeat(level * opts.indent_width());
-        line = format!("{}{}", indent, line.trim());
-        indented.push_str(&line);
-        indented.push('\n');
-        // increase indent for THEN, BEGIN, CASE, opening parenthesis
-        if trimmed.starts_with("THEN")
-            || trimmed.starts_with("BEGIN")
-            || trimmed.starts_with("CASE")
-            || line.contains('(')
-        {
-            level += 1;
+        match tok.text.to_ascii_lowercase().as_str() {
+            "begin" | "case" => *level += 1,
+            "end" => *level = level.saturating_sub(1),
+            _ => {}
         }
-        // heuristic: reduce for single-line END
-        if trimmed.starts_with("END ") || trimmed == "END" {
-            if level > 0 {
-                level = level.saturating_sub(1);
+    }
+}
+
+/// Clause keywords the old regex pipeline broke a line at when it ran past
+/// `opts.line_width`. Kept as whole-word markers with surrounding spaces so
+/// a match can't land inside an identifier (e.g. `offset` inside
+/// `read_offset`).
+const CLAUSE_BREAKS: &[&str] = &[
+    " SET ",
+    " VALUES ",
+    " WHERE ",
+    " FROM ",
+    " GROUP BY ",
+    " ORDER BY ",
+    " HAVING ",
+    " LIMIT ",
+    " OFFSET ",
+    " JOIN ",
+    " INNER JOIN ",
+    " LEFT JOIN ",
+    " RIGHT JOIN ",
+    " FULL JOIN ",
+    " AND ",
+    " OR ",
+];
+
+/// Wraps one already-indented, already-cased line once it overflows
+/// `opts.line_width`: splits at the first clause-break marker within the
+/// width budget, or failing that at the last comma before it, indenting
+/// every continuation line one level deeper than `base_indent`. Mirrors the
+/// pre-series regex pipeline's `clause_breaks` wrapping, which is the only
+/// width control a statement with no AST (every `Other` statement) gets.
+fn wrap_overlong_line(line: &str, base_indent: &str, opts: &Options) -> Vec<String> {
+    if line.len() <= opts.line_width {
+        return vec![line.to_string()];
+    }
+
+    let continuation_indent = format!("{}{}", base_indent, " ".repeat(opts.indent_width()));
+    let mut out = Vec::new();
+    let mut rest = line.to_string();
+    let mut first = true;
+    while rest.len() > opts.line_width {
+        let mut split_at = None;
+        for marker in CLAUSE_BREAKS {
+            if let Some(pos) = rest.find(marker) {
+                if pos > 0 && pos < opts.line_width {
+                    split_at = Some(pos);
+                    break;
+                }
+            }
+        }
+        if split_at.is_none() {
+            if let Some(pos) = rest[..opts.line_width].rfind(',') {
+                split_at = Some(pos + 1);
             }
         }
+        let idx = split_at.unwrap_or(opts.line_width);
+        let (head, tail) = rest.split_at(idx);
+        if first {
+            out.push(head.trim_end().to_string());
+            first = false;
+        } else {
+            out.push(format!("{}{}", continuation_indent, head.trim()));
+        }
+        rest = tail.trim().to_string();
+    }
+    if !rest.is_empty() {
+        out.push(format!("{}{}", continuation_indent, rest));
+    }
+    out
+}
+
+fn starts_with_word(upper: &str, word: &str) -> bool {
+    upper.strip_prefix(word).is_some_and(|rest| {
+        rest.is_empty() || !rest.starts_with(|c: char| c.is_alphanumeric() || c == '_')
+    })
+}
+
+fn push_space(out: &mut String) {
+    if !out.is_empty() && !out.ends_with(' ') && !out.ends_with('\n') {
+        out.push(' ');
     }
+}
 
-    Ok(indented.trim_end().to_string())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::{CommaStyle, IndentStyle};
+
+    fn opts() -> Options {
+        Options {
+            line_width: 88,
+            indent: IndentStyle::Two,
+            uppercase_keywords: true,
+            comma_style: CommaStyle::Trailing,
+        }
+    }
+
+    /// Formats in full, ignoring any `--file-lines` restriction - what all
+    /// the tests below want, since none of them pass a path.
+    fn format_sql(input: &str, opts: &Options) -> Result<String> {
+        format_sql_restricted(input, opts, None, &FileLines::default())
+    }
+
+    #[test]
+    fn string_literal_contents_are_never_cased_or_respaced() {
+        let out = format_sql("select 'select from where' from t;", &opts()).unwrap();
+        assert_eq!(out, "SELECT 'select from where' FROM t;");
+    }
+
+    #[test]
+    fn operator_inside_a_string_literal_is_not_given_spacing() {
+        let out = format_sql("select email from t where email='a=b@example.com';", &opts()).unwrap();
+        assert_eq!(out, "SELECT email FROM t WHERE email = 'a=b@example.com';");
+    }
+
+    #[test]
+    fn line_comment_text_is_left_verbatim() {
+        let out = format_sql("begin\n  local i real; -- select from where a=b\nend;", &opts()).unwrap();
+        assert!(out.contains("-- select from where a=b"));
+    }
+
+    #[test]
+    fn doubled_quote_escape_survives_a_full_format_pass() {
+        let out = format_sql("select name from t where name = 'o''brien';", &opts()).unwrap();
+        assert_eq!(out, "SELECT name FROM t WHERE name = 'o''brien';");
+    }
+
+    #[test]
+    fn quoted_identifier_contents_are_preserved() {
+        let out = format_sql(r#"select "select" from t;"#, &opts()).unwrap();
+        assert_eq!(out, r#"SELECT "select" FROM t;"#);
+    }
+
+    #[test]
+    fn nested_begin_end_blocks_indent_one_level_per_nesting() {
+        let out = format_sql(
+            "BEGIN\n  LOCAL i real;\n  BEGIN\n    LOCAL j real;\n    SELECT i, j FROM t;\n  END;\nEND;",
+            &opts(),
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            "BEGIN\n  LOCAL i REAL;\n  BEGIN\n    LOCAL j REAL;\n    SELECT i, j FROM t;\n  END;\nEND;"
+        );
+    }
+
+    #[test]
+    fn bare_star_projection_gets_doc_based_layout_not_the_flat_other_fallback() {
+        // A width tight enough to force every clause onto its own line -
+        // only reachable if `*` parsed into the AST instead of falling
+        // back to `Statement::Other`, which has no notion of clauses.
+        let narrow = Options {
+            line_width: 5,
+            indent: IndentStyle::Two,
+            uppercase_keywords: true,
+            comma_style: CommaStyle::Trailing,
+        };
+        let out = format_sql("select * from accounts;", &narrow).unwrap();
+        assert_eq!(out, "SELECT\n  *\nFROM\n  accounts;");
+    }
+
+    #[test]
+    fn overlong_dml_statement_wraps_at_clause_boundaries() {
+        // `UPDATE` has no AST and is always `Statement::Other`; it still
+        // needs width control, the same naive clause-break wrap the old
+        // regex pipeline applied to every line.
+        let out = format_sql(
+            "UPDATE accounts SET balance = balance + 100, status = 'active', updated_at = now() WHERE id = 1 AND region = 'us-east-1';",
+            &opts(),
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            "UPDATE accounts\n  SET balance = balance + 100, status = 'active', updated_at = now()\n  WHERE id = 1 AND region = 'us-east-1';"
+        );
+    }
 }